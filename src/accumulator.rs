@@ -0,0 +1,136 @@
+use std::ops::{Add, Div};
+
+use ndarray::Array1;
+use num_traits::{FromPrimitive, Zero};
+
+use crate::{bandpass, Spectra};
+
+/// How [`SpectrogramAccumulator`] combines a new block's bandpass with the running estimate.
+pub enum AccumulationMode {
+    /// Weight every block equally, as if all blocks seen since the last [`SpectrogramAccumulator::reset`]
+    /// were averaged together at once.
+    AllAveraging,
+    /// Exponentially down-weight older blocks, like a sound-level meter, with time constant `tau`
+    /// (in the same units as the accumulator's `cadence`).
+    Exponential { tau: f32 },
+}
+
+/// A streaming accumulator for near-real-time bandpass monitoring: ingests successive [`Spectra`]
+/// blocks and maintains a running average bandpass, suitable for a live telescope monitor or
+/// waterfall display.
+pub struct SpectrogramAccumulator {
+    mode: AccumulationMode,
+    /// The time, in the same units as `tau`, between successive blocks pushed into this
+    /// accumulator. Only used by [`AccumulationMode::Exponential`].
+    cadence: f32,
+    current: Option<Array1<f32>>,
+    blocks_seen: usize,
+    history: Option<Vec<Array1<f32>>>,
+}
+
+impl SpectrogramAccumulator {
+    /// Creates a new accumulator. Set `keep_history` to retain every intermediate bandpass
+    /// estimate (e.g. to build a waterfall plot); otherwise only the current estimate is kept.
+    pub fn new(mode: AccumulationMode, cadence: f32, keep_history: bool) -> Self {
+        Self {
+            mode,
+            cadence,
+            current: None,
+            blocks_seen: 0,
+            history: keep_history.then(Vec::new),
+        }
+    }
+
+    /// Folds a new block's bandpass into the running estimate.
+    pub fn push<T>(&mut self, spectra: &Spectra<T>)
+    where
+        T: Clone + Zero + FromPrimitive + Add<Output = T> + Div<Output = T>,
+        f32: std::convert::From<T>,
+    {
+        let block = bandpass(spectra).mapv(f32::from);
+        let updated = match (&self.current, &self.mode) {
+            (None, _) => block,
+            (Some(current), AccumulationMode::AllAveraging) => {
+                let n = self.blocks_seen as f32;
+                (current * n + &block) / (n + 1.0)
+            }
+            (Some(current), AccumulationMode::Exponential { tau }) => {
+                let alpha = 1.0 - (-self.cadence / *tau).exp();
+                current * (1.0 - alpha) + &block * alpha
+            }
+        };
+        self.blocks_seen += 1;
+        self.current = Some(updated.clone());
+        if let Some(history) = &mut self.history {
+            history.push(updated);
+        }
+    }
+
+    /// The current running bandpass estimate, or `None` if no blocks have been pushed since
+    /// creation or the last [`Self::reset`].
+    pub fn current(&self) -> Option<&Array1<f32>> {
+        self.current.as_ref()
+    }
+
+    /// Every intermediate bandpass estimate pushed since creation or the last [`Self::reset`], in
+    /// order, for building a waterfall/spectrogram display. Empty unless `keep_history` was set in
+    /// [`Self::new`].
+    pub fn history(&self) -> &[Array1<f32>] {
+        self.history.as_deref().unwrap_or_default()
+    }
+
+    /// Clears the running estimate and any retained history, as if the accumulator were freshly
+    /// created.
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.blocks_seen = 0;
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_spectra;
+
+    #[test]
+    fn test_all_averaging_equally_weights_blocks() {
+        let mut accumulator =
+            SpectrogramAccumulator::new(AccumulationMode::AllAveraging, 1.0, false);
+
+        let first: Vec<u16> = vec![0, 0, 0, 0];
+        let second: Vec<u16> = vec![2, 2, 2, 2];
+        accumulator.push(&to_spectra(&first, 4));
+        accumulator.push(&to_spectra(&second, 4));
+
+        assert_eq!(accumulator.current().unwrap(), &Array1::from(vec![1.0; 4]));
+    }
+
+    #[test]
+    fn test_reset_clears_state() {
+        let mut accumulator =
+            SpectrogramAccumulator::new(AccumulationMode::AllAveraging, 1.0, true);
+        let block: Vec<u16> = vec![1, 2, 3, 4];
+        accumulator.push(&to_spectra(&block, 4));
+        accumulator.reset();
+
+        assert!(accumulator.current().is_none());
+        assert!(accumulator.history().is_empty());
+    }
+
+    #[test]
+    fn test_exponential_mode_down_weights_history() {
+        let mut accumulator =
+            SpectrogramAccumulator::new(AccumulationMode::Exponential { tau: 1.0 }, 1.0, false);
+
+        let first: Vec<u16> = vec![0, 0];
+        let second: Vec<u16> = vec![10, 10];
+        accumulator.push(&to_spectra(&first, 2));
+        accumulator.push(&to_spectra(&second, 2));
+
+        let current = accumulator.current().unwrap();
+        assert!(current[0] > 0.0 && current[0] < 10.0);
+    }
+}
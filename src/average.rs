@@ -0,0 +1,80 @@
+use ndarray::{Array1, Array2, ArrayView1};
+
+use crate::Spectra;
+
+/// A mask-aware, optionally-weighted average over the time axis of `spectra`, for folding many
+/// integrations together without masked RFI (or un-trusted low-weight samples) biasing the
+/// result.
+///
+/// `weights` gives a per-sample weight (e.g. radiometer/[`crate::Tsys`]-derived weights); when
+/// omitted every sample is weighted equally, same as [`crate::bandpass`]. `mask` gives a
+/// per-`(sample, channel)` flag (typically the output of a [`crate::Filter`]) excluding that cell
+/// from both the sum and the weight it would otherwise contribute.
+///
+/// Each channel's result is renormalized by the summed weight of its surviving cells; a channel
+/// with no surviving cells is returned as `NaN`.
+pub fn average<T>(
+    spectra: &Spectra<T>,
+    weights: Option<ArrayView1<T>>,
+    mask: Option<&Array2<bool>>,
+) -> Array1<f32>
+where
+    T: Clone,
+    f32: std::convert::From<T>,
+{
+    let (samples, channels) = spectra.dim();
+    let data = spectra.mapv(f32::from);
+    let weights: Array1<f32> = match weights {
+        Some(w) => w.mapv(f32::from),
+        None => Array1::ones(samples),
+    };
+
+    let mut sums = Array1::<f32>::zeros(channels);
+    let mut weight_sums = Array1::<f32>::zeros(channels);
+    for sample in 0..samples {
+        let weight = weights[sample];
+        for channel in 0..channels {
+            if mask.is_some_and(|m| m[[sample, channel]]) {
+                continue;
+            }
+            sums[channel] += weight * data[[sample, channel]];
+            weight_sums[channel] += weight;
+        }
+    }
+
+    sums / weight_sums
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::to_spectra;
+
+    #[test]
+    fn test_average_unweighted_unmasked_matches_bandpass() {
+        let raw: Vec<f32> = vec![1.0, 2.0, 3.0, 4.0, 1.0, 2.0, 3.0, 4.0, 1.0, 2.0, 3.0, 4.0];
+        let spectra = to_spectra(&raw, 4);
+        let avg = average(&spectra, None, None);
+        assert_eq!(avg, array![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_average_excludes_masked_cells() {
+        let raw: Vec<f32> = vec![1.0, 2.0, 100.0, 4.0];
+        let spectra = to_spectra(&raw, 2);
+        let mask = array![[false, false], [true, false]];
+        let avg = average(&spectra, None, Some(&mask));
+        assert_eq!(avg, array![1.0, 3.0]);
+    }
+
+    #[test]
+    fn test_average_applies_weights() {
+        let raw: Vec<f32> = vec![1.0, 2.0, 3.0, 4.0];
+        let spectra = to_spectra(&raw, 2);
+        let weights: Vec<f32> = vec![1.0, 3.0];
+        let avg = average(&spectra, Some(ArrayView1::from(&weights)), None);
+        assert_eq!(avg, array![2.5, 3.5]);
+    }
+}
@@ -0,0 +1,165 @@
+use ndarray::{Array1, Array2};
+
+/// The family of smooth curves [`baseline`] fits to a bandpass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaselineModel {
+    /// An ordinary power-series polynomial: `a0 + a1*x + a2*x^2 + ...`.
+    Polynomial,
+    /// A sum of Chebyshev polynomials of the first kind, which conditions better than a plain
+    /// power series at higher orders.
+    Chebyshev,
+}
+
+/// Fits a smooth `model` of the given `order` to a channel `bandpass`, so the instrument's
+/// spectral response can be removed before RFI flagging. Channels whose index falls inside any
+/// of the inclusive `exclude` ranges (e.g. known spectral lines or persistent RFI) are left out
+/// of the fit, though the returned arrays still cover every channel.
+///
+/// Returns `(fit, residual)`, where `residual = bandpass - fit`.
+pub fn baseline<T>(
+    bandpass: &Array1<T>,
+    order: usize,
+    model: BaselineModel,
+    exclude: &[(usize, usize)],
+) -> (Array1<f32>, Array1<f32>)
+where
+    T: Clone,
+    f32: std::convert::From<T>,
+{
+    let channels = bandpass.len();
+    let y = bandpass.mapv(f32::from);
+
+    let design_row = |channel: usize| -> Array1<f32> {
+        let x = normalize(channel, channels);
+        match model {
+            BaselineModel::Polynomial => Array1::from_iter((0..=order).map(|p| x.powi(p as i32))),
+            BaselineModel::Chebyshev => chebyshev_terms(x, order),
+        }
+    };
+
+    let n_terms = order + 1;
+    let mut ata = Array2::<f32>::zeros((n_terms, n_terms));
+    let mut aty = Array1::<f32>::zeros(n_terms);
+    for channel in (0..channels).filter(|c| !is_excluded(*c, exclude)) {
+        let row = design_row(channel);
+        for i in 0..n_terms {
+            aty[i] += row[i] * y[channel];
+            for j in 0..n_terms {
+                ata[[i, j]] += row[i] * row[j];
+            }
+        }
+    }
+
+    let coefficients = solve_normal_equations(ata, aty);
+    let fit = Array1::from_iter((0..channels).map(|c| design_row(c).dot(&coefficients)));
+    let residual = &y - &fit;
+    (fit, residual)
+}
+
+fn is_excluded(channel: usize, exclude: &[(usize, usize)]) -> bool {
+    exclude
+        .iter()
+        .any(|&(lo, hi)| channel >= lo && channel <= hi)
+}
+
+/// Maps a channel index onto `[-1, 1]`, which keeps both the polynomial and Chebyshev design
+/// matrices well-conditioned regardless of how many channels there are.
+fn normalize(channel: usize, channels: usize) -> f32 {
+    if channels <= 1 {
+        0.0
+    } else {
+        -1.0 + 2.0 * channel as f32 / (channels - 1) as f32
+    }
+}
+
+/// The Chebyshev polynomials of the first kind `T_0(x), ..., T_order(x)` via the standard
+/// recurrence `T_n = 2*x*T_{n-1} - T_{n-2}`.
+fn chebyshev_terms(x: f32, order: usize) -> Array1<f32> {
+    let mut terms = Vec::with_capacity(order + 1);
+    terms.push(1.0);
+    if order >= 1 {
+        terms.push(x);
+        for n in 2..=order {
+            terms.push(2.0 * x * terms[n - 1] - terms[n - 2]);
+        }
+    }
+    Array1::from(terms)
+}
+
+/// Solves the least-squares normal equations `a * x = b` by Gaussian elimination with partial
+/// pivoting. `a` is expected to be the (small, `n_terms x n_terms`) Gram matrix built by
+/// [`baseline`], not the full design matrix.
+fn solve_normal_equations(mut a: Array2<f32>, mut b: Array1<f32>) -> Array1<f32> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[[i, col]].abs().partial_cmp(&a[[j, col]].abs()).unwrap())
+            .unwrap();
+        if pivot != col {
+            for k in 0..n {
+                let tmp = a[[col, k]];
+                a[[col, k]] = a[[pivot, k]];
+                a[[pivot, k]] = tmp;
+            }
+            b.swap(col, pivot);
+        }
+
+        let diag = a[[col, col]];
+        assert!(
+            diag.abs() > f32::EPSILON,
+            "singular normal-equations matrix; reduce `order` or widen `exclude`"
+        );
+        for row in (col + 1)..n {
+            let factor = a[[row, col]] / diag;
+            for k in col..n {
+                a[[row, k]] -= factor * a[[col, k]];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = Array1::<f32>::zeros(n);
+    for row in (0..n).rev() {
+        let sum: f32 = (row + 1..n).map(|k| a[[row, k]] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[[row, row]];
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn test_baseline_polynomial_fits_a_line() {
+        let bandpass: Array1<f32> = array![1.0, 3.0, 5.0, 7.0, 9.0];
+        let (fit, residual) = baseline(&bandpass, 1, BaselineModel::Polynomial, &[]);
+        for (f, b) in fit.iter().zip(bandpass.iter()) {
+            assert!((f - b).abs() < 1e-4);
+        }
+        for r in residual.iter() {
+            assert!(r.abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_baseline_excludes_a_spectral_line() {
+        // A flat bandpass with a single spiked channel that should be excluded from the fit.
+        let bandpass: Array1<f32> = array![2.0, 2.0, 2.0, 50.0, 2.0, 2.0, 2.0];
+        let (fit, _) = baseline(&bandpass, 0, BaselineModel::Polynomial, &[(3, 3)]);
+        for f in fit.iter() {
+            assert!((f - 2.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_baseline_chebyshev_fits_a_line() {
+        let bandpass: Array1<f32> = array![1.0, 3.0, 5.0, 7.0, 9.0];
+        let (fit, _) = baseline(&bandpass, 1, BaselineModel::Chebyshev, &[]);
+        for (f, b) in fit.iter().zip(bandpass.iter()) {
+            assert!((f - b).abs() < 1e-4);
+        }
+    }
+}
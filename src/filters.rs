@@ -0,0 +1,267 @@
+use ndarray::{Array2, ArrayView1, ArrayViewMut1, Axis, Zip};
+use ndarray_stats::{interpolate::Midpoint, Quantile1dExt};
+use noisy_float::types::{n32, n64, N32};
+use num_traits::{FromPrimitive, Zero};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use crate::{bandpass, Filter, Spectra};
+
+/// A filter to remove system-temperature-based bandpass. `tolerance` indicated what fraction of the median to clip.
+pub struct Tsys {
+    pub tolerance: f32,
+}
+
+impl<T> Filter<T> for Tsys
+where
+    T: Clone
+        + Zero
+        + FromPrimitive
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Div<Output = T>
+        + Mul<Output = T>
+        + Ord
+        + Rem<Output = T>,
+    f32: std::convert::From<T>,
+{
+    fn mask(&self, spectra: Spectra<T>) -> Array2<bool> {
+        let t_sys = bandpass(&spectra);
+        let t_sys_median = f32::from(t_sys.clone().quantile_mut(n64(0.5), &Midpoint).unwrap());
+        let t_sys_mask = t_sys.mapv(|v| f32::from(v) < self.tolerance * t_sys_median);
+        let (samples, channels) = spectra.dim();
+        t_sys_mask
+            .broadcast((samples, channels))
+            .unwrap()
+            .to_owned()
+    }
+}
+
+/// The SumThreshold RFI filter of Offringa et al., operated independently along the frequency
+/// and time axes and OR-ed together.
+///
+/// `spectra` is expected to already be a residual (e.g. the spectra with the fit returned by
+/// [`crate::baseline`] subtracted from every sample) - SumThreshold looks for samples that stick out above the noise, not the smooth bandpass shape
+/// itself.
+pub struct SumThreshold {
+    /// The largest window length to try; windows are tried at M = 1, 2, 4, ..., up to this value.
+    pub m_max: usize,
+    /// The factor by which the per-length threshold shrinks as the window doubles in length.
+    pub rho: f32,
+    /// The base threshold (for M = 1), expressed as a multiple of the robust noise estimate.
+    pub chi1: f32,
+}
+
+impl Default for SumThreshold {
+    fn default() -> Self {
+        Self {
+            m_max: 64,
+            rho: 1.5,
+            chi1: 6.0,
+        }
+    }
+}
+
+impl<T> Filter<T> for SumThreshold
+where
+    T: Clone,
+    f32: std::convert::From<T>,
+{
+    fn mask(&self, spectra: Spectra<T>) -> Array2<bool> {
+        let residual = spectra.mapv(f32::from);
+        let sigma = 1.4826 * mad(&residual);
+        let t1 = self.chi1 * sigma;
+
+        let freq_mask = sumthreshold_axis(&residual, Axis(1), t1, self.rho, self.m_max);
+        let time_mask = sumthreshold_axis(&residual, Axis(0), t1, self.rho, self.m_max);
+        freq_mask | time_mask
+    }
+}
+
+/// Run the SumThreshold sweep independently over every lane along `axis`, starting from a fresh
+/// mask so the frequency and time passes don't see each other's flags, and return every lane's
+/// flags together as one mask.
+fn sumthreshold_axis(
+    residual: &Array2<f32>,
+    axis: Axis,
+    t1: f32,
+    rho: f32,
+    m_max: usize,
+) -> Array2<bool> {
+    let mut mask = Array2::from_elem(residual.dim(), false);
+    Zip::from(residual.lanes(axis))
+        .and(mask.lanes_mut(axis))
+        .for_each(|lane, lane_mask| {
+            sumthreshold_lane(lane, lane_mask, t1, rho, m_max);
+        });
+    mask
+}
+
+/// The 1D SumThreshold sweep: for each window length M = 1, 2, 4, ..., slide a length-M window
+/// along `lane`, flagging it whenever the magnitude of the sum of its not-yet-flagged residuals
+/// exceeds `M * t_M`.
+fn sumthreshold_lane(
+    lane: ArrayView1<f32>,
+    mut lane_mask: ArrayViewMut1<bool>,
+    t1: f32,
+    rho: f32,
+    m_max: usize,
+) {
+    let n = lane.len();
+    let mut m = 1usize;
+    while m <= m_max && m <= n {
+        let t_m = t1 * rho.powf(-(m as f32).log2());
+        for start in 0..=(n - m) {
+            let window = start..start + m;
+            let sum: f32 = window
+                .clone()
+                .filter(|&i| !lane_mask[i])
+                .map(|i| lane[i])
+                .sum();
+            if sum.abs() > m as f32 * t_m {
+                for i in window {
+                    lane_mask[i] = true;
+                }
+            }
+        }
+        m *= 2;
+    }
+}
+
+/// A filter to excise channels whose power statistics are non-Gaussian, based on the generalized
+/// Spectral Kurtosis estimator of Nita & Gary. Unlike [`Tsys`] this needs no smooth-bandpass
+/// assumption, since RFI skews the *shape* of the power distribution even when it doesn't move
+/// the mean much.
+pub struct SpectralKurtosis {
+    /// Flag the channel when its SK estimator falls below this value.
+    pub lower: f32,
+    /// Flag the channel when its SK estimator falls above this value.
+    pub upper: f32,
+}
+
+impl SpectralKurtosis {
+    /// Sensible `[lower, upper]` bounds for `m` accumulated power samples per channel, taken as
+    /// the 3-sigma points of the Gaussian approximation to the SK distribution (SK ~ 1, with
+    /// variance ~ 4/m).
+    pub fn from_accumulations(m: usize) -> Self {
+        let sigma = 3.0 * (4.0 / m as f32).sqrt();
+        Self {
+            lower: 1.0 - sigma,
+            upper: 1.0 + sigma,
+        }
+    }
+}
+
+impl<T> Filter<T> for SpectralKurtosis
+where
+    T: Clone,
+    f32: std::convert::From<T>,
+{
+    fn mask(&self, spectra: Spectra<T>) -> Array2<bool> {
+        let power = spectra.mapv(f32::from);
+        let m = power.nrows() as f32;
+        let s1 = power.sum_axis(Axis(0));
+        let s2 = power.mapv(|v| v * v).sum_axis(Axis(0));
+
+        let sk = Zip::from(&s1)
+            .and(&s2)
+            .map_collect(|&s1, &s2| ((m + 1.0) / (m - 1.0)) * (m * s2 / (s1 * s1) - 1.0));
+        let channel_mask = sk.mapv(|v| v < self.lower || v > self.upper);
+
+        let (samples, channels) = spectra.dim();
+        channel_mask
+            .broadcast((samples, channels))
+            .unwrap()
+            .to_owned()
+    }
+}
+
+/// The median absolute deviation of every element of `data`, used as a robust noise estimate.
+fn mad(data: &Array2<f32>) -> f32 {
+    let mut values: Vec<N32> = data.iter().map(|&v| n32(v)).collect();
+    let med = median(&mut values);
+    let mut deviations: Vec<N32> = values.iter().map(|v| n32((v.raw() - med).abs())).collect();
+    median(&mut deviations)
+}
+
+fn median(values: &mut [N32]) -> f32 {
+    values.sort_unstable();
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2].raw()
+    } else {
+        (values[n / 2 - 1].raw() + values[n / 2].raw()) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::to_spectra;
+
+    #[test]
+    fn test_tsys_mask() {
+        let raw: Vec<u16> = vec![1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3, 4];
+        let spectra = to_spectra(&raw, 4);
+        let filter = Tsys { tolerance: 0.6 };
+        let mask = filter.mask(spectra);
+        assert_eq!(
+            mask,
+            array![
+                [true, false, false, false],
+                [true, false, false, false],
+                [true, false, false, false]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsys_mask_unsorted_bandpass() {
+        let raw: Vec<u16> = vec![4, 1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3];
+        let spectra = to_spectra(&raw, 4);
+        let filter = Tsys { tolerance: 0.6 };
+        let mask = filter.mask(spectra);
+        assert_eq!(
+            mask,
+            array![
+                [false, true, false, false],
+                [false, true, false, false],
+                [false, true, false, false]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spectral_kurtosis_flags_steady_channel() {
+        // Channel 0 is constant (SK collapses to 0, far below a real accumulation's lower
+        // bound); channel 1 has exponentially-distributed power around the same mean, as a
+        // well-behaved noise channel would.
+        let noisy: [f32; 64] = [
+            4.080, 0.101, 1.286, 1.010, 5.334, 4.517, 8.909, 0.364, 2.192, 0.121, 0.987, 2.816,
+            0.108, 0.887, 4.198, 3.149, 0.996, 3.559, 6.631, 0.026, 6.556, 4.791, 1.664, 0.676,
+            12.606, 1.641, 0.389, 0.407, 7.522, 3.703, 6.583, 5.233, 3.073, 14.465, 1.903, 3.212,
+            7.074, 3.855, 7.914, 3.445, 4.877, 0.188, 1.035, 1.367, 0.333, 1.060, 0.426, 1.303,
+            4.039, 1.815, 1.849, 0.940, 1.242, 11.037, 4.177, 3.758, 0.751, 5.224, 0.714, 1.909,
+            18.234, 4.087, 3.256, 4.616,
+        ];
+        let raw: Vec<f32> = noisy.iter().flat_map(|&v| [4.0, v]).collect();
+        let spectra = to_spectra(&raw, 2);
+        let filter = SpectralKurtosis::from_accumulations(spectra.nrows());
+        let mask = filter.mask(spectra);
+        assert!(mask[[0, 0]]);
+        assert!(!mask[[0, 1]]);
+    }
+
+    #[test]
+    fn test_sumthreshold_flags_spike() {
+        let raw: Vec<f32> = vec![
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 50.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        ];
+        let spectra = to_spectra(&raw, 8);
+        let filter = SumThreshold::default();
+        let mask = filter.mask(spectra);
+        assert!(mask[[1, 0]]);
+        assert!(!mask[[0, 1]]);
+    }
+}
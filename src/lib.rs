@@ -1,10 +1,20 @@
-use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::ops::{Add, Div};
 
 use ndarray::{aview1, Array1, Array2, ArrayView2, Axis};
-use ndarray_stats::{interpolate::Midpoint, Quantile1dExt};
-use noisy_float::types::n64;
 use num_traits::{FromPrimitive, Zero};
 
+mod accumulator;
+mod average;
+mod baseline;
+mod filters;
+mod stokes;
+
+pub use accumulator::{AccumulationMode, SpectrogramAccumulator};
+pub use average::average;
+pub use baseline::{baseline, BaselineModel};
+pub use filters::{SpectralKurtosis, SumThreshold, Tsys};
+pub use stokes::{stokes, SignConvention, StokesParameters};
+
 /// The ndarray container for spectral data.
 /// Subsequent frequnecy channels are aligned in memory and ndarray is "C/Python" style,
 /// as such, this has dimensions (samples, channels)
@@ -26,37 +36,11 @@ where
 }
 
 /// Creates a 2D `Spectra` from an array of raw measurements
-pub fn to_spectra<T>(raw_spectra: &[T], channels: usize) -> Spectra<T> {
+pub fn to_spectra<T>(raw_spectra: &[T], channels: usize) -> Spectra<'_, T> {
     let samples = raw_spectra.len() / channels;
     aview1(raw_spectra).into_shape((samples, channels)).unwrap()
 }
 
-/// A filter to remove system-temperature-based bandpass. `tolerance` indicated what fraction of the median to clip.
-pub struct Tsys {
-    pub tolerance: f32,
-}
-
-impl<T> Filter<T> for Tsys
-where
-    T: Clone
-        + Zero
-        + FromPrimitive
-        + Add<Output = T>
-        + Sub<Output = T>
-        + Div<Output = T>
-        + Mul<Output = T>
-        + Ord
-        + Rem<Output = T>,
-    f32: std::convert::From<T>,
-{
-    fn mask(&self, spectra: Spectra<T>) -> Array2<bool> {
-        let mut t_sys = bandpass(&spectra);
-        let t_sys_median = f32::from(t_sys.quantile_mut(n64(0.5), &Midpoint).unwrap());
-        let t_sys_mask = t_sys.mapv(|v| f32::from(v) < self.tolerance * t_sys_median);
-        todo!()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use ndarray::array;
@@ -0,0 +1,126 @@
+use ndarray::{Array2, Zip};
+
+use crate::Spectra;
+
+/// The sign convention used when combining the cross-polarization term into Stokes U and V.
+/// Different correlators and feed conventions disagree on the sign of V in particular, so this
+/// is left configurable rather than hard-coded.
+#[derive(Debug, Clone, Copy)]
+pub struct SignConvention {
+    pub u_sign: f32,
+    pub v_sign: f32,
+}
+
+impl Default for SignConvention {
+    fn default() -> Self {
+        Self {
+            u_sign: 1.0,
+            v_sign: -1.0,
+        }
+    }
+}
+
+/// The four Stokes parameters, one `(samples, channels)` array apiece.
+pub struct StokesParameters {
+    pub i: Array2<f32>,
+    pub q: Array2<f32>,
+    pub u: Array2<f32>,
+    pub v: Array2<f32>,
+}
+
+impl StokesParameters {
+    /// Propagates a mask (typically produced by running a [`crate::Filter`] over `i`) to every
+    /// Stokes product, replacing flagged cells with `NaN`. This lets a single RFI pass over total
+    /// power clean the whole polarization set.
+    pub fn apply_mask(&mut self, mask: &Array2<bool>) {
+        for product in [&mut self.i, &mut self.q, &mut self.u, &mut self.v] {
+            Zip::from(product).and(mask).for_each(|value, &flagged| {
+                if flagged {
+                    *value = f32::NAN;
+                }
+            });
+        }
+    }
+}
+
+/// Derives Stokes I, Q, U, V from the four raw dual-polarization products GReX records per
+/// channel: the two auto-correlations `xx`, `yy`, and the real/imaginary parts of the
+/// cross-correlation `xy`.
+///
+/// `I = XX + YY`, `Q = XX - YY`, `U = 2*Re(XY)`, `V = -2*Im(XY)`, with the sign of `U` and `V`
+/// controlled by `sign`.
+pub fn stokes<T>(
+    xx: Spectra<T>,
+    yy: Spectra<T>,
+    xy_re: Spectra<T>,
+    xy_im: Spectra<T>,
+    sign: SignConvention,
+) -> StokesParameters
+where
+    T: Clone,
+    f32: std::convert::From<T>,
+{
+    let xx = xx.mapv(f32::from);
+    let yy = yy.mapv(f32::from);
+    let xy_re = xy_re.mapv(f32::from);
+    let xy_im = xy_im.mapv(f32::from);
+
+    let i = &xx + &yy;
+    let q = &xx - &yy;
+    let u = &xy_re * (2.0 * sign.u_sign);
+    let v = &xy_im * (2.0 * sign.v_sign);
+
+    StokesParameters { i, q, u, v }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::to_spectra;
+
+    #[test]
+    fn test_stokes_default_convention() {
+        let xx: Vec<f32> = vec![3.0, 1.0];
+        let yy: Vec<f32> = vec![1.0, 1.0];
+        let xy_re: Vec<f32> = vec![2.0, 0.0];
+        let xy_im: Vec<f32> = vec![4.0, 0.0];
+
+        let stokes = stokes(
+            to_spectra(&xx, 2),
+            to_spectra(&yy, 2),
+            to_spectra(&xy_re, 2),
+            to_spectra(&xy_im, 2),
+            SignConvention::default(),
+        );
+
+        assert_eq!(stokes.i, array![[4.0, 2.0]]);
+        assert_eq!(stokes.q, array![[2.0, 0.0]]);
+        assert_eq!(stokes.u, array![[4.0, 0.0]]);
+        assert_eq!(stokes.v, array![[-8.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_stokes_apply_mask_propagates_to_all_products() {
+        let xx: Vec<f32> = vec![3.0, 1.0];
+        let yy: Vec<f32> = vec![1.0, 1.0];
+        let xy_re: Vec<f32> = vec![2.0, 0.0];
+        let xy_im: Vec<f32> = vec![4.0, 0.0];
+
+        let mut stokes = stokes(
+            to_spectra(&xx, 2),
+            to_spectra(&yy, 2),
+            to_spectra(&xy_re, 2),
+            to_spectra(&xy_im, 2),
+            SignConvention::default(),
+        );
+        stokes.apply_mask(&array![[true, false]]);
+
+        assert!(stokes.i[[0, 0]].is_nan());
+        assert!(stokes.q[[0, 0]].is_nan());
+        assert!(stokes.u[[0, 0]].is_nan());
+        assert!(stokes.v[[0, 0]].is_nan());
+        assert_eq!(stokes.i[[0, 1]], 2.0);
+    }
+}